@@ -0,0 +1,278 @@
+//! A compact binary wire format for the intermediate representation.
+//!
+//! The disassembly frontend hands the [`Project`](super::Project) over to the rest of the
+//! pipeline either as human-readable JSON (useful for debugging, enabled through the `json`
+//! feature) or, by default, through the binary format defined here. For binaries that lift to
+//! millions of [`Def`](super::Def)s the JSON encoding is both slow to parse and expensive in
+//! peak memory, since serde_json builds an intermediate `Value` tree before it ever reaches our
+//! structs. The binary format instead writes straight into a `Vec<u8>` and reads straight back
+//! out of a byte slice, with no intermediate representation.
+//!
+//! Two traits drive the format:
+//! - [`ToBytes`] encodes a value, and reports how many bytes that encoding will take so callers
+//!   can preallocate the output buffer.
+//! - [`FromBytes`] decodes a value from the front of a byte slice, advancing the slice past the
+//!   bytes it consumed.
+//!
+//! Primitive scalars (such as [`ByteSize`](super::ByteSize)) are encoded as fixed-width
+//! big-endian integers. Every `Vec<T>` or `BTreeMap<K, V>` of terms is instead prefixed with a
+//! varint length, using the same continuation-bit scheme as LEB128: the low 7 bits of each byte
+//! hold payload, and the high bit is set while more bytes follow. A collection of fewer than 128
+//! elements therefore costs a single length byte.
+
+use crate::prelude::*;
+use std::collections::BTreeMap;
+
+/// A value that can be encoded into the compact binary wire format.
+pub trait ToBytes {
+    /// Append the binary encoding of `self` to `out`.
+    fn to_bytes(&self, out: &mut Vec<u8>);
+
+    /// The exact number of bytes that [`to_bytes`](ToBytes::to_bytes) will append.
+    ///
+    /// Used by callers (most importantly the disassembly frontend) to preallocate the output
+    /// buffer for a whole [`Project`](super::Project) up front instead of letting `Vec::push`
+    /// reallocate repeatedly while walking a binary with millions of terms.
+    fn count_bytes(&self) -> usize;
+}
+
+/// A value that can be decoded from the compact binary wire format.
+pub trait FromBytes: Sized {
+    /// Decode a value from the front of `input`, advancing `input` past the consumed bytes.
+    fn from_bytes(input: &mut &[u8]) -> Result<Self>;
+}
+
+/// Write `value` as a varint, using the LEB128 continuation-bit scheme.
+///
+/// The low 7 bits of each emitted byte hold payload bits, least-significant group first; the
+/// high bit is set on every byte except the last.
+pub fn write_varint(mut value: u64, out: &mut Vec<u8>) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+/// The number of bytes [`write_varint`] would emit for `value`.
+pub fn varint_len(value: u64) -> usize {
+    let mut value = value;
+    let mut len = 1;
+    while value > 0x7f {
+        value >>= 7;
+        len += 1;
+    }
+    len
+}
+
+/// Read a varint written by [`write_varint`] from the front of `input`.
+pub fn read_varint(input: &mut &[u8]) -> Result<u64> {
+    let mut result: u64 = 0;
+    let mut shift = 0;
+    loop {
+        let (&byte, rest) = input
+            .split_first()
+            .ok_or_else(|| anyhow!("Unexpected end of input while reading a varint"))?;
+        *input = rest;
+        result |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(result);
+        }
+        shift += 7;
+        if shift >= 64 {
+            return Err(anyhow!("Varint is too large to fit into a u64"));
+        }
+    }
+}
+
+/// Split off the first `len` bytes of `input`, advancing `input` past them.
+pub(crate) fn split_bytes<'a>(input: &mut &'a [u8], len: usize) -> Result<&'a [u8]> {
+    if input.len() < len {
+        return Err(anyhow!("Unexpected end of input"));
+    }
+    let (head, tail) = input.split_at(len);
+    *input = tail;
+    Ok(head)
+}
+
+impl ToBytes for u64 {
+    fn to_bytes(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_be_bytes());
+    }
+
+    fn count_bytes(&self) -> usize {
+        8
+    }
+}
+
+impl FromBytes for u64 {
+    fn from_bytes(input: &mut &[u8]) -> Result<Self> {
+        let bytes = split_bytes(input, 8)?;
+        Ok(u64::from_be_bytes(bytes.try_into().unwrap()))
+    }
+}
+
+impl ToBytes for bool {
+    fn to_bytes(&self, out: &mut Vec<u8>) {
+        out.push(*self as u8);
+    }
+
+    fn count_bytes(&self) -> usize {
+        1
+    }
+}
+
+impl FromBytes for bool {
+    fn from_bytes(input: &mut &[u8]) -> Result<Self> {
+        let bytes = split_bytes(input, 1)?;
+        Ok(bytes[0] != 0)
+    }
+}
+
+impl ToBytes for String {
+    fn to_bytes(&self, out: &mut Vec<u8>) {
+        write_varint(self.len() as u64, out);
+        out.extend_from_slice(self.as_bytes());
+    }
+
+    fn count_bytes(&self) -> usize {
+        varint_len(self.len() as u64) + self.len()
+    }
+}
+
+impl FromBytes for String {
+    fn from_bytes(input: &mut &[u8]) -> Result<Self> {
+        let len = read_varint(input)? as usize;
+        let bytes = split_bytes(input, len)?;
+        Ok(String::from_utf8(bytes.to_vec())?)
+    }
+}
+
+impl<T: ToBytes> ToBytes for Vec<T> {
+    fn to_bytes(&self, out: &mut Vec<u8>) {
+        write_varint(self.len() as u64, out);
+        for element in self {
+            element.to_bytes(out);
+        }
+    }
+
+    fn count_bytes(&self) -> usize {
+        varint_len(self.len() as u64) + self.iter().map(ToBytes::count_bytes).sum::<usize>()
+    }
+}
+
+impl<T: FromBytes> FromBytes for Vec<T> {
+    fn from_bytes(input: &mut &[u8]) -> Result<Self> {
+        let len = read_varint(input)? as usize;
+        // `len` comes straight off the wire and is not yet trustworthy: a truncated or corrupted
+        // input could claim a length far larger than `input` actually has bytes for. Every
+        // element costs at least one byte, so `input.len()` is always a safe upper bound on how
+        // many elements can really follow; capping the preallocation on it turns a would-be
+        // multi-gigabyte allocation attempt into a graceful `Err` once `T::from_bytes` runs out
+        // of input instead.
+        let mut result = Vec::with_capacity(len.min(input.len()));
+        for _ in 0..len {
+            result.push(T::from_bytes(input)?);
+        }
+        Ok(result)
+    }
+}
+
+impl<K: ToBytes + Ord, V: ToBytes> ToBytes for BTreeMap<K, V> {
+    fn to_bytes(&self, out: &mut Vec<u8>) {
+        write_varint(self.len() as u64, out);
+        for (key, value) in self {
+            key.to_bytes(out);
+            value.to_bytes(out);
+        }
+    }
+
+    fn count_bytes(&self) -> usize {
+        varint_len(self.len() as u64)
+            + self
+                .iter()
+                .map(|(key, value)| key.count_bytes() + value.count_bytes())
+                .sum::<usize>()
+    }
+}
+
+impl<K: FromBytes + Ord, V: FromBytes> FromBytes for BTreeMap<K, V> {
+    fn from_bytes(input: &mut &[u8]) -> Result<Self> {
+        let len = read_varint(input)? as usize;
+        let mut result = BTreeMap::new();
+        for _ in 0..len {
+            let key = K::from_bytes(input)?;
+            let value = V::from_bytes(input)?;
+            result.insert(key, value);
+        }
+        Ok(result)
+    }
+}
+
+impl<T: ToBytes> ToBytes for Option<T> {
+    fn to_bytes(&self, out: &mut Vec<u8>) {
+        match self {
+            Some(value) => {
+                out.push(1);
+                value.to_bytes(out);
+            }
+            None => out.push(0),
+        }
+    }
+
+    fn count_bytes(&self) -> usize {
+        1 + self.as_ref().map(ToBytes::count_bytes).unwrap_or(0)
+    }
+}
+
+impl<T: FromBytes> FromBytes for Option<T> {
+    fn from_bytes(input: &mut &[u8]) -> Result<Self> {
+        let bytes = split_bytes(input, 1)?;
+        match bytes[0] {
+            0 => Ok(None),
+            1 => Ok(Some(T::from_bytes(input)?)),
+            tag => Err(anyhow!("Invalid `Option` tag byte: {tag}")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn varint_round_trip() {
+        for value in [0u64, 1, 127, 128, 300, u32::MAX as u64, u64::MAX] {
+            let mut bytes = Vec::new();
+            write_varint(value, &mut bytes);
+            assert_eq!(bytes.len(), varint_len(value));
+            let mut slice = bytes.as_slice();
+            assert_eq!(read_varint(&mut slice).unwrap(), value);
+            assert!(slice.is_empty());
+        }
+    }
+
+    #[test]
+    fn short_vec_costs_one_length_byte() {
+        let values: Vec<u64> = vec![1, 2, 3];
+        let mut bytes = Vec::new();
+        values.to_bytes(&mut bytes);
+        assert_eq!(bytes.len(), values.count_bytes());
+        // One varint length byte followed by 3 fixed-width u64s.
+        assert_eq!(bytes.len(), 1 + 3 * 8);
+    }
+
+    #[test]
+    fn vec_round_trip() {
+        let values: Vec<u64> = vec![1, 2, 300, 70_000];
+        let mut bytes = Vec::new();
+        values.to_bytes(&mut bytes);
+        let mut slice = bytes.as_slice();
+        assert_eq!(Vec::<u64>::from_bytes(&mut slice).unwrap(), values);
+        assert!(slice.is_empty());
+    }
+}
@@ -0,0 +1,205 @@
+use super::{ByteSize, FromBytes, ToBytes};
+use crate::prelude::*;
+use std::ops::Deref;
+
+/// A bitvector is a fixed-width, arbitrary-precision value, used to represent the concrete
+/// values that show up as constants or as the result of constant folding in the intermediate
+/// representation.
+///
+/// Internally a `Bitvector` just wraps an [`apint::ApInt`], which already provides the
+/// two's-complement arithmetic we need. This type exists so that the rest of the IR does not
+/// depend directly on the `apint` crate and so that we can attach our own (de)serialization.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct Bitvector(apint::ApInt);
+
+#[cfg(feature = "json")]
+impl Serialize for Bitvector {
+    /// Serialize as a hex string of our own compact binary encoding, since `apint::ApInt` has no
+    /// native serde support and a plain decimal string would throw away the width needed to
+    /// reconstruct the value. Only compiled in for the human-readable JSON debug path.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut bytes = Vec::with_capacity(self.count_bytes());
+        self.to_bytes(&mut bytes);
+        serializer.serialize_str(&to_hex(&bytes))
+    }
+}
+
+#[cfg(feature = "json")]
+impl<'de> Deserialize<'de> for Bitvector {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let string = String::deserialize(deserializer)?;
+        let bytes = from_hex(&string).map_err(serde::de::Error::custom)?;
+        let mut slice = bytes.as_slice();
+        Bitvector::from_bytes(&mut slice).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Encode `bytes` as a lowercase hex string.
+#[cfg(feature = "json")]
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Decode a lowercase hex string produced by [`to_hex`].
+#[cfg(feature = "json")]
+fn from_hex(hex: &str) -> Result<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return Err(anyhow!("Hex-encoded bitvector has an odd number of digits"));
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            u8::from_str_radix(&hex[i..i + 2], 16)
+                .map_err(|err| anyhow!("Invalid hex digit in encoded bitvector: {err}"))
+        })
+        .collect()
+}
+
+impl Deref for Bitvector {
+    type Target = apint::ApInt;
+
+    fn deref(&self) -> &apint::ApInt {
+        &self.0
+    }
+}
+
+impl From<apint::ApInt> for Bitvector {
+    fn from(apint: apint::ApInt) -> Bitvector {
+        Bitvector(apint)
+    }
+}
+
+impl From<Bitvector> for apint::ApInt {
+    fn from(bitvector: Bitvector) -> apint::ApInt {
+        bitvector.0
+    }
+}
+
+impl Bitvector {
+    /// Create a zero-valued bitvector of the given width.
+    pub fn zero(width: ByteSize) -> Bitvector {
+        Bitvector(apint::ApInt::zero(width.into()))
+    }
+
+    /// The width of the bitvector, rounded up to the next full byte.
+    pub fn width(&self) -> ByteSize {
+        self.0.width().into()
+    }
+
+    /// Returns the parity of `self`: `true` iff `self` contains an odd number of set bits over
+    /// its whole width.
+    ///
+    /// This is the generic hardware reduction behind flags like x86's `PF`. Lifting `PF`
+    /// specifically requires reducing over only the low byte of the result, so callers should
+    /// narrow the value to that byte themselves (e.g. via `Subpiece`) before calling this.
+    pub fn xor_reduce(&self) -> bool {
+        self.0.count_ones() % 2 == 1
+    }
+
+    /// Returns `true` iff at least one bit of `self` is set.
+    ///
+    /// This is the complement of the zero flag: `!bitvector.any()` is exactly the condition
+    /// lifted for `ZF`.
+    pub fn any(&self) -> bool {
+        !self.0.is_zero()
+    }
+
+    /// Returns `true` iff every bit of `self` is set, i.e. `self` is all-ones for its width.
+    pub fn all(&self) -> bool {
+        self.0.is_all_set()
+    }
+
+    /// Returns the number of set bits in `self`, i.e. its population count.
+    pub fn count_ones(&self) -> u64 {
+        self.0.count_ones() as u64
+    }
+}
+
+impl ToBytes for Bitvector {
+    fn to_bytes(&self, out: &mut Vec<u8>) {
+        let width = self.width();
+        width.to_bytes(out);
+        for limb in self.0.as_ref().as_slice() {
+            out.extend_from_slice(&limb.to_le_bytes());
+        }
+    }
+
+    fn count_bytes(&self) -> usize {
+        let num_limbs = self.0.as_ref().as_slice().len();
+        self.width().count_bytes() + num_limbs * std::mem::size_of::<u64>()
+    }
+}
+
+impl FromBytes for Bitvector {
+    fn from_bytes(input: &mut &[u8]) -> Result<Self> {
+        let width = ByteSize::from_bytes(input)?;
+        let num_limbs = (width.as_bit_length() + 63) / 64;
+        // `width` (and thus `num_limbs`) comes straight off the wire and is not yet trustworthy:
+        // 8 corrupted bytes can claim a width of `u64::MAX`, which would otherwise try to
+        // preallocate exabytes of limbs before `u64::from_bytes` ever gets a chance to fail on
+        // the truncated input. Each limb costs 8 bytes on the wire, so `input.len() / 8 + 1` is a
+        // safe upper bound on how many limbs can really follow.
+        let mut limbs = Vec::with_capacity(num_limbs.min(input.len() / 8 + 1));
+        for _ in 0..num_limbs {
+            limbs.push(u64::from_bytes(input)?);
+        }
+        let apint = apint::ApInt::from_iter(limbs)
+            .map_err(|err| anyhow!("Failed to reconstruct a bitvector from its limbs: {err}"))?
+            .into_truncate(width)
+            .map_err(|err| anyhow!("Failed to truncate a reconstructed bitvector: {err}"))?;
+        Ok(Bitvector(apint))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn xor_reduce_is_the_parity_of_the_whole_value() {
+        let even_parity = Bitvector::from(apint::ApInt::from_u32(0b0000_0011));
+        assert!(!even_parity.xor_reduce());
+        let odd_parity = Bitvector::from(apint::ApInt::from_u32(0b0000_0111));
+        assert!(odd_parity.xor_reduce());
+
+        // A set bit outside the low byte must still flip the parity.
+        let odd_parity_high_bit = Bitvector::from(apint::ApInt::from_u32(0b1_0000_0000));
+        assert!(odd_parity_high_bit.xor_reduce());
+    }
+
+    #[test]
+    fn any_and_all() {
+        let zero = Bitvector::zero(ByteSize::new(4));
+        assert!(!zero.any());
+        assert!(!zero.all());
+        let all_ones = Bitvector::from(apint::ApInt::all_set(apint::BitWidth::new(8).unwrap()));
+        assert!(all_ones.any());
+        assert!(all_ones.all());
+    }
+
+    #[test]
+    fn count_ones_is_the_popcount() {
+        let value = Bitvector::from(apint::ApInt::from_u32(0b1011_0100));
+        assert_eq!(value.count_ones(), 4);
+    }
+
+    #[test]
+    fn binary_round_trip() {
+        let value = Bitvector::from(apint::ApInt::from_u64(0x1234_5678_9abc_def0));
+        let mut bytes = Vec::new();
+        value.to_bytes(&mut bytes);
+        assert_eq!(bytes.len(), value.count_bytes());
+        let mut slice = bytes.as_slice();
+        assert_eq!(Bitvector::from_bytes(&mut slice).unwrap(), value);
+        assert!(slice.is_empty());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json_debug_round_trip() {
+        let value = Bitvector::from(apint::ApInt::from_u64(0x1234_5678_9abc_def0));
+        let json = serde_json::to_string(&value).unwrap();
+        let deserialized: Bitvector = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, value);
+    }
+}
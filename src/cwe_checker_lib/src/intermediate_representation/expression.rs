@@ -0,0 +1,469 @@
+use super::{BitSize, Bitvector, ByteSize, FromBytes, ToBytes, Variable};
+use crate::prelude::*;
+
+/// An expression is a calculation rule
+/// on how to compute a certain value given some variables (register values) as input.
+///
+/// The basic building blocks of expressions are the same as for most other intermediate
+/// representations for binaries:
+/// variables, constants, unary and binary operations and a few expressions more.
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub enum Expression {
+    /// A variable
+    Var(Variable),
+    /// A constant value
+    Const(Bitvector),
+    /// A binary operation
+    BinOp {
+        /// The operation type
+        op: BinOpType,
+        /// The left hand side expression
+        lhs: Box<Expression>,
+        /// The right hand side expression
+        rhs: Box<Expression>,
+    },
+    /// A unary operation
+    UnOp {
+        /// The operation type
+        op: UnOpType,
+        /// The argument expression
+        arg: Box<Expression>,
+    },
+    /// A cast operation, e.g. sign- or zero-extension of an integer
+    Cast {
+        /// The cast operation type
+        op: CastOpType,
+        /// The size in bytes of the result.
+        ///
+        /// Unlike `Subpiece`, a cast always produces a whole, byte-aligned register-sized value
+        /// (e.g. zero/sign-extending to the next integer width), so `ByteSize` loses no
+        /// precision here; non-byte-aligned bitfield extracts are expressed as `Subpiece`
+        /// instead. Deliberately left as `ByteSize` rather than `BitSize` for this reason, even
+        /// though `Subpiece` was switched.
+        size: ByteSize,
+        /// The argument expression
+        arg: Box<Expression>,
+    },
+    /// An unknown value, e.g. the result of an operation not representable in the IR
+    Unknown {
+        /// A human-readable description of the unknown value
+        description: String,
+        /// The size in bytes of the value
+        size: ByteSize,
+    },
+    /// Extract a sub-piece of a value, given the offset and size of the sub-piece in bits.
+    ///
+    /// Both `low_bit` and `size` are bit-precise, so a `Subpiece` can express a non-byte-aligned
+    /// bitfield extract (e.g. a single status-register flag or a packed SIMD lane) without
+    /// conservatively widening it to the next full byte.
+    Subpiece {
+        /// The offset in bits of the low bit of the sub-piece
+        low_bit: BitSize,
+        /// The size in bits of the sub-piece
+        size: BitSize,
+        /// The argument expression
+        arg: Box<Expression>,
+    },
+}
+
+/// The type of binary operation of a binary operation expression.
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum BinOpType {
+    /// Concatenate two values
+    Piece,
+    /// Equality check
+    IntEqual,
+    /// Inequality check
+    IntNotEqual,
+    /// Unsigned less-than check
+    IntLess,
+    /// Unsigned less-than-or-equal check
+    IntLessEqual,
+    /// Signed less-than check
+    IntSLess,
+    /// Signed less-than-or-equal check
+    IntSLessEqual,
+    /// Addition
+    IntAdd,
+    /// Subtraction
+    IntSub,
+    /// Bitwise AND
+    IntAnd,
+    /// Bitwise OR
+    IntOr,
+    /// Bitwise XOR
+    IntXOr,
+    /// Multiplication
+    IntMult,
+    /// Unsigned division
+    IntDiv,
+    /// Signed division
+    IntSDiv,
+    /// Unsigned remainder
+    IntRem,
+    /// Signed remainder
+    IntSRem,
+    /// Left shift
+    IntLeft,
+    /// Unsigned (logical) right shift
+    IntRight,
+    /// Signed (arithmetic) right shift
+    IntSRight,
+    /// Boolean XOR
+    BoolXOr,
+    /// Boolean AND
+    BoolAnd,
+    /// Boolean OR
+    BoolOr,
+}
+
+/// The type of unary operation of a unary operation expression.
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum UnOpType {
+    /// Two's complement negation
+    IntNegate,
+    /// Bitwise complement
+    IntNot,
+    /// Boolean negation
+    BoolNegate,
+}
+
+/// The type of cast operation of a cast expression.
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum CastOpType {
+    /// Zero-extend to a wider integer
+    IntZExt,
+    /// Sign-extend to a wider integer
+    IntSExt,
+    /// Convert an integer to the nearest representable float
+    IntToFloat,
+    /// Round a float to the nearest integer
+    FloatToInt,
+}
+
+impl Expression {
+    /// The exact size in bits of the value that this expression computes.
+    ///
+    /// Unlike [`bytesize`](Expression::bytesize), this does not widen a non-byte-aligned
+    /// `Subpiece` to the next full byte, so analyses that care about bit-precise fields (single
+    /// status-register flags, packed SIMD lanes, ...) should use this instead of `bytesize`.
+    pub fn bitsize(&self) -> BitSize {
+        use Expression::*;
+        match self {
+            Var(var) => var.size.into(),
+            Const(bitvector) => (**bitvector).width().into(),
+            BinOp { op, lhs, rhs } => match op {
+                BinOpType::IntEqual
+                | BinOpType::IntNotEqual
+                | BinOpType::IntLess
+                | BinOpType::IntLessEqual
+                | BinOpType::IntSLess
+                | BinOpType::IntSLessEqual
+                | BinOpType::BoolXOr
+                | BinOpType::BoolAnd
+                | BinOpType::BoolOr => BitSize::new(1),
+                BinOpType::Piece => lhs.bitsize() + rhs.bitsize(),
+                _ => lhs.bitsize(),
+            },
+            UnOp { arg, .. } => arg.bitsize(),
+            Cast { size, .. } | Unknown { size, .. } => (*size).into(),
+            Subpiece { size, .. } => *size,
+        }
+    }
+
+    /// The size in bytes of the value that this expression computes, rounded up to the next full
+    /// byte if it is not byte-aligned.
+    ///
+    /// This conservative, whole-byte view is what register and memory sizes are expressed in
+    /// throughout the rest of the IR. Use [`bitsize`](Expression::bitsize) instead wherever exact
+    /// bit precision matters.
+    pub fn bytesize(&self) -> ByteSize {
+        self.bitsize().round_up_to_byte_size()
+    }
+}
+
+trait FromBytesRawU8 {
+    fn from_bytes_raw(input: &mut &[u8]) -> Result<u8>;
+}
+
+impl FromBytesRawU8 for u8 {
+    fn from_bytes_raw(input: &mut &[u8]) -> Result<u8> {
+        let (&byte, rest) = input
+            .split_first()
+            .ok_or_else(|| anyhow!("Unexpected end of input while reading a tag byte"))?;
+        *input = rest;
+        Ok(byte)
+    }
+}
+
+impl ToBytes for BinOpType {
+    fn to_bytes(&self, out: &mut Vec<u8>) {
+        use BinOpType::*;
+        out.push(match self {
+            Piece => 0,
+            IntEqual => 1,
+            IntNotEqual => 2,
+            IntLess => 3,
+            IntLessEqual => 4,
+            IntSLess => 5,
+            IntSLessEqual => 6,
+            IntAdd => 7,
+            IntSub => 8,
+            IntAnd => 9,
+            IntOr => 10,
+            IntXOr => 11,
+            IntMult => 12,
+            IntDiv => 13,
+            IntSDiv => 14,
+            IntRem => 15,
+            IntSRem => 16,
+            IntLeft => 17,
+            IntRight => 18,
+            IntSRight => 19,
+            BoolXOr => 20,
+            BoolAnd => 21,
+            BoolOr => 22,
+        });
+    }
+
+    fn count_bytes(&self) -> usize {
+        1
+    }
+}
+
+impl FromBytes for BinOpType {
+    fn from_bytes(input: &mut &[u8]) -> Result<Self> {
+        use BinOpType::*;
+        Ok(match u8::from_bytes_raw(input)? {
+            0 => Piece,
+            1 => IntEqual,
+            2 => IntNotEqual,
+            3 => IntLess,
+            4 => IntLessEqual,
+            5 => IntSLess,
+            6 => IntSLessEqual,
+            7 => IntAdd,
+            8 => IntSub,
+            9 => IntAnd,
+            10 => IntOr,
+            11 => IntXOr,
+            12 => IntMult,
+            13 => IntDiv,
+            14 => IntSDiv,
+            15 => IntRem,
+            16 => IntSRem,
+            17 => IntLeft,
+            18 => IntRight,
+            19 => IntSRight,
+            20 => BoolXOr,
+            21 => BoolAnd,
+            22 => BoolOr,
+            tag => return Err(anyhow!("Invalid tag byte for `BinOpType`: {tag}")),
+        })
+    }
+}
+
+impl ToBytes for UnOpType {
+    fn to_bytes(&self, out: &mut Vec<u8>) {
+        use UnOpType::*;
+        out.push(match self {
+            IntNegate => 0,
+            IntNot => 1,
+            BoolNegate => 2,
+        });
+    }
+
+    fn count_bytes(&self) -> usize {
+        1
+    }
+}
+
+impl FromBytes for UnOpType {
+    fn from_bytes(input: &mut &[u8]) -> Result<Self> {
+        use UnOpType::*;
+        Ok(match u8::from_bytes_raw(input)? {
+            0 => IntNegate,
+            1 => IntNot,
+            2 => BoolNegate,
+            tag => return Err(anyhow!("Invalid tag byte for `UnOpType`: {tag}")),
+        })
+    }
+}
+
+impl ToBytes for CastOpType {
+    fn to_bytes(&self, out: &mut Vec<u8>) {
+        use CastOpType::*;
+        out.push(match self {
+            IntZExt => 0,
+            IntSExt => 1,
+            IntToFloat => 2,
+            FloatToInt => 3,
+        });
+    }
+
+    fn count_bytes(&self) -> usize {
+        1
+    }
+}
+
+impl FromBytes for CastOpType {
+    fn from_bytes(input: &mut &[u8]) -> Result<Self> {
+        use CastOpType::*;
+        Ok(match u8::from_bytes_raw(input)? {
+            0 => IntZExt,
+            1 => IntSExt,
+            2 => IntToFloat,
+            3 => FloatToInt,
+            tag => return Err(anyhow!("Invalid tag byte for `CastOpType`: {tag}")),
+        })
+    }
+}
+
+impl ToBytes for Expression {
+    fn to_bytes(&self, out: &mut Vec<u8>) {
+        match self {
+            Expression::Var(var) => {
+                out.push(0);
+                var.to_bytes(out);
+            }
+            Expression::Const(bitvector) => {
+                out.push(1);
+                bitvector.to_bytes(out);
+            }
+            Expression::BinOp { op, lhs, rhs } => {
+                out.push(2);
+                op.to_bytes(out);
+                lhs.to_bytes(out);
+                rhs.to_bytes(out);
+            }
+            Expression::UnOp { op, arg } => {
+                out.push(3);
+                op.to_bytes(out);
+                arg.to_bytes(out);
+            }
+            Expression::Cast { op, size, arg } => {
+                out.push(4);
+                op.to_bytes(out);
+                size.to_bytes(out);
+                arg.to_bytes(out);
+            }
+            Expression::Unknown { description, size } => {
+                out.push(5);
+                description.to_bytes(out);
+                size.to_bytes(out);
+            }
+            Expression::Subpiece { low_bit, size, arg } => {
+                out.push(6);
+                low_bit.to_bytes(out);
+                size.to_bytes(out);
+                arg.to_bytes(out);
+            }
+        }
+    }
+
+    fn count_bytes(&self) -> usize {
+        1 + match self {
+            Expression::Var(var) => var.count_bytes(),
+            Expression::Const(bitvector) => bitvector.count_bytes(),
+            Expression::BinOp { op, lhs, rhs } => {
+                op.count_bytes() + lhs.count_bytes() + rhs.count_bytes()
+            }
+            Expression::UnOp { op, arg } => op.count_bytes() + arg.count_bytes(),
+            Expression::Cast { op, size, arg } => {
+                op.count_bytes() + size.count_bytes() + arg.count_bytes()
+            }
+            Expression::Unknown { description, size } => {
+                description.count_bytes() + size.count_bytes()
+            }
+            Expression::Subpiece { low_bit, size, arg } => {
+                low_bit.count_bytes() + size.count_bytes() + arg.count_bytes()
+            }
+        }
+    }
+}
+
+impl FromBytes for Expression {
+    fn from_bytes(input: &mut &[u8]) -> Result<Self> {
+        let tag = u8::from_bytes_raw(input)?;
+        Ok(match tag {
+            0 => Expression::Var(Variable::from_bytes(input)?),
+            1 => Expression::Const(Bitvector::from_bytes(input)?),
+            2 => Expression::BinOp {
+                op: BinOpType::from_bytes(input)?,
+                lhs: Box::new(Expression::from_bytes(input)?),
+                rhs: Box::new(Expression::from_bytes(input)?),
+            },
+            3 => Expression::UnOp {
+                op: UnOpType::from_bytes(input)?,
+                arg: Box::new(Expression::from_bytes(input)?),
+            },
+            4 => Expression::Cast {
+                op: CastOpType::from_bytes(input)?,
+                size: ByteSize::from_bytes(input)?,
+                arg: Box::new(Expression::from_bytes(input)?),
+            },
+            5 => Expression::Unknown {
+                description: String::from_bytes(input)?,
+                size: ByteSize::from_bytes(input)?,
+            },
+            6 => Expression::Subpiece {
+                low_bit: BitSize::from_bytes(input)?,
+                size: BitSize::from_bytes(input)?,
+                arg: Box::new(Expression::from_bytes(input)?),
+            },
+            tag => return Err(anyhow!("Invalid tag byte for `Expression`: {tag}")),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn binary_round_trip() {
+        let expr = Expression::BinOp {
+            op: BinOpType::IntAdd,
+            lhs: Box::new(Expression::Var(Variable {
+                name: "RAX".into(),
+                size: ByteSize::new(8),
+                is_temp: false,
+            })),
+            rhs: Box::new(Expression::Const(Bitvector::zero(ByteSize::new(8)))),
+        };
+        let mut bytes = Vec::new();
+        expr.to_bytes(&mut bytes);
+        assert_eq!(bytes.len(), expr.count_bytes());
+        let mut slice = bytes.as_slice();
+        assert_eq!(Expression::from_bytes(&mut slice).unwrap(), expr);
+        assert!(slice.is_empty());
+    }
+
+    #[test]
+    fn subpiece_is_bit_precise() {
+        let flag_bit = Expression::Subpiece {
+            low_bit: BitSize::new(6),
+            size: BitSize::new(1),
+            arg: Box::new(Expression::Var(Variable {
+                name: "EFLAGS".into(),
+                size: ByteSize::new(4),
+                is_temp: false,
+            })),
+        };
+        // The exact bit count is preserved for analyses that need it...
+        assert_eq!(flag_bit.bitsize(), BitSize::new(1));
+        // ...while the conservative byte view still rounds up to a whole byte.
+        assert_eq!(flag_bit.bytesize(), ByteSize::new(1));
+
+        let mut bytes = Vec::new();
+        flag_bit.to_bytes(&mut bytes);
+        assert_eq!(bytes.len(), flag_bit.count_bytes());
+        let mut slice = bytes.as_slice();
+        assert_eq!(Expression::from_bytes(&mut slice).unwrap(), flag_bit);
+        assert!(slice.is_empty());
+    }
+}
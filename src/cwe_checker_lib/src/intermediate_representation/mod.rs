@@ -10,6 +10,9 @@
 use crate::prelude::*;
 use derive_more::*;
 
+mod binary_format;
+pub use binary_format::*;
+mod parse;
 mod bitvector;
 pub use bitvector::*;
 mod variable;
@@ -24,9 +27,9 @@ pub use term::*;
 /// Used to represent sizes of values in registers or in memory.
 /// Can also be used for other byte-valued numbers, like offsets,
 /// as long as the number is guaranteed to be non-negative.
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "json", serde(transparent))]
 #[derive(
-    Serialize,
-    Deserialize,
     Debug,
     PartialEq,
     Eq,
@@ -35,7 +38,6 @@ pub use term::*;
     Hash,
     Clone,
     Copy,
-    Display,
     Binary,
     Octal,
     LowerHex,
@@ -59,9 +61,24 @@ pub use term::*;
     ShlAssign,
     Sum,
 )]
-#[serde(transparent)]
 pub struct ByteSize(u64);
 
+impl ToBytes for ByteSize {
+    fn to_bytes(&self, out: &mut Vec<u8>) {
+        self.0.to_bytes(out);
+    }
+
+    fn count_bytes(&self) -> usize {
+        self.0.count_bytes()
+    }
+}
+
+impl FromBytes for ByteSize {
+    fn from_bytes(input: &mut &[u8]) -> Result<Self> {
+        Ok(ByteSize(u64::from_bytes(input)?))
+    }
+}
+
 impl From<ByteSize> for apint::BitWidth {
     fn from(bytesize: ByteSize) -> apint::BitWidth {
         apint::BitWidth::from((u64::from(bytesize) * 8) as usize)
@@ -87,29 +104,171 @@ impl ByteSize {
     }
 }
 
+/// An unsigned number of bits.
+///
+/// Unlike [`ByteSize`], a `BitSize` can represent sub-byte widths exactly, which is needed when
+/// lifting bitfield extracts, status-register slices, or packed SIMD lanes that do not align to
+/// a byte boundary. Prefer `ByteSize` for anything that is guaranteed to be byte-aligned (e.g.
+/// the size of a register or a memory access); use `BitSize` only where bit-level precision
+/// actually matters.
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "json", serde(transparent))]
+#[derive(
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Clone,
+    Copy,
+    Display,
+    From,
+    Into,
+    Add,
+    Sub,
+    AddAssign,
+    SubAssign,
+)]
+pub struct BitSize(u64);
+
+impl ToBytes for BitSize {
+    fn to_bytes(&self, out: &mut Vec<u8>) {
+        self.0.to_bytes(out);
+    }
+
+    fn count_bytes(&self) -> usize {
+        self.0.count_bytes()
+    }
+}
+
+impl FromBytes for BitSize {
+    fn from_bytes(input: &mut &[u8]) -> Result<Self> {
+        Ok(BitSize(u64::from_bytes(input)?))
+    }
+}
+
+impl From<BitSize> for apint::BitWidth {
+    /// Lossless, since both `BitSize` and `apint::BitWidth` count individual bits.
+    fn from(bitsize: BitSize) -> apint::BitWidth {
+        apint::BitWidth::from(u64::from(bitsize) as usize)
+    }
+}
+
+impl From<apint::BitWidth> for BitSize {
+    /// Lossless, since both `BitSize` and `apint::BitWidth` count individual bits.
+    fn from(bitwidth: apint::BitWidth) -> BitSize {
+        BitSize::new(bitwidth.to_usize() as u64)
+    }
+}
+
+impl From<ByteSize> for BitSize {
+    fn from(bytesize: ByteSize) -> BitSize {
+        BitSize::new(u64::from(bytesize) * 8)
+    }
+}
+
+impl BitSize {
+    /// Create a new `BitSize` object
+    pub fn new(value: u64) -> BitSize {
+        BitSize(value)
+    }
+
+    /// Convert to the smallest `ByteSize` that can hold `self`, rounding up to the nearest full
+    /// byte if `self` is not byte-aligned.
+    pub fn round_up_to_byte_size(self) -> ByteSize {
+        ByteSize::new((u64::from(self) + 7) / 8)
+    }
+
+    /// Convert to the equivalent `ByteSize`, or `None` if `self` is not byte-aligned.
+    pub fn to_byte_size(self) -> Option<ByteSize> {
+        if u64::from(self) % 8 == 0 {
+            Some(ByteSize::new(u64::from(self) / 8))
+        } else {
+            None
+        }
+    }
+}
+
 /// Properties of C/C++ data types such as size.
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+///
+/// Populated from architecture-specific configuration files. Each size is given as a
+/// human-readable string, e.g. `"8KiB"` (see the [`parse`] module), rather than as a raw byte
+/// count, so that config files stay easy to read and review.
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
 pub struct DatatypeProperties {
     /// Holds the size of the char type
+    #[cfg_attr(feature = "json", serde(with = "parse"))]
     pub char_size: ByteSize,
     /// Holds the size of the double type
+    #[cfg_attr(feature = "json", serde(with = "parse"))]
     pub double_size: ByteSize,
     /// Holds the size of the float type
+    #[cfg_attr(feature = "json", serde(with = "parse"))]
     pub float_size: ByteSize,
     /// Holds the size of the integer type
+    #[cfg_attr(feature = "json", serde(with = "parse"))]
     pub integer_size: ByteSize,
     /// Holds the size of the long double type
+    #[cfg_attr(feature = "json", serde(with = "parse"))]
     pub long_double_size: ByteSize,
     /// Holds the size of the long long type
+    #[cfg_attr(feature = "json", serde(with = "parse"))]
     pub long_long_size: ByteSize,
     /// Holds the size of the long type
+    #[cfg_attr(feature = "json", serde(with = "parse"))]
     pub long_size: ByteSize,
     /// Holds the size of the pointer type
+    #[cfg_attr(feature = "json", serde(with = "parse"))]
     pub pointer_size: ByteSize,
     /// Holds the size of the short type
+    #[cfg_attr(feature = "json", serde(with = "parse"))]
     pub short_size: ByteSize,
 }
 
+impl ToBytes for DatatypeProperties {
+    fn to_bytes(&self, out: &mut Vec<u8>) {
+        self.char_size.to_bytes(out);
+        self.double_size.to_bytes(out);
+        self.float_size.to_bytes(out);
+        self.integer_size.to_bytes(out);
+        self.long_double_size.to_bytes(out);
+        self.long_long_size.to_bytes(out);
+        self.long_size.to_bytes(out);
+        self.pointer_size.to_bytes(out);
+        self.short_size.to_bytes(out);
+    }
+
+    fn count_bytes(&self) -> usize {
+        self.char_size.count_bytes()
+            + self.double_size.count_bytes()
+            + self.float_size.count_bytes()
+            + self.integer_size.count_bytes()
+            + self.long_double_size.count_bytes()
+            + self.long_long_size.count_bytes()
+            + self.long_size.count_bytes()
+            + self.pointer_size.count_bytes()
+            + self.short_size.count_bytes()
+    }
+}
+
+impl FromBytes for DatatypeProperties {
+    fn from_bytes(input: &mut &[u8]) -> Result<Self> {
+        Ok(DatatypeProperties {
+            char_size: ByteSize::from_bytes(input)?,
+            double_size: ByteSize::from_bytes(input)?,
+            float_size: ByteSize::from_bytes(input)?,
+            integer_size: ByteSize::from_bytes(input)?,
+            long_double_size: ByteSize::from_bytes(input)?,
+            long_long_size: ByteSize::from_bytes(input)?,
+            long_size: ByteSize::from_bytes(input)?,
+            pointer_size: ByteSize::from_bytes(input)?,
+            short_size: ByteSize::from_bytes(input)?,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use apint::BitWidth;
@@ -126,4 +285,37 @@ mod tests {
 
         assert_eq!(ByteSize::new(2).as_bit_length(), 16);
     }
+
+    #[test]
+    fn bit_size_is_lossless_unlike_byte_size() {
+        let bits: BitWidth = BitWidth::new(12).unwrap();
+        let bit_size: BitSize = bits.into();
+        assert_eq!(u64::from(bit_size), 12);
+        let roundtripped: BitWidth = bit_size.into();
+        assert_eq!(roundtripped.to_usize(), 12);
+
+        assert_eq!(bit_size.to_byte_size(), None);
+        assert_eq!(bit_size.round_up_to_byte_size(), ByteSize::new(2));
+        assert_eq!(BitSize::new(16).to_byte_size(), Some(ByteSize::new(2)));
+    }
+
+    #[test]
+    fn byte_size_binary_round_trip() {
+        let size = ByteSize::new(0x1234);
+        let mut bytes = Vec::new();
+        size.to_bytes(&mut bytes);
+        assert_eq!(bytes.len(), size.count_bytes());
+        let mut slice = bytes.as_slice();
+        assert_eq!(ByteSize::from_bytes(&mut slice).unwrap(), size);
+        assert!(slice.is_empty());
+    }
+
+    #[test]
+    fn byte_size_human_readable_parsing_and_display() {
+        assert_eq!("2KiB".parse::<ByteSize>().unwrap(), ByteSize::new(2 * 1024));
+        assert_eq!("4".parse::<ByteSize>().unwrap(), ByteSize::new(4));
+        assert!("not_a_size".parse::<ByteSize>().is_err());
+
+        assert_eq!(ByteSize::new(8 * 1024).to_string(), "8KiB");
+    }
 }
\ No newline at end of file
@@ -0,0 +1,148 @@
+//! Human-readable parsing and formatting for [`ByteSize`](super::ByteSize).
+//!
+//! Configuration files describe type sizes and other byte-valued thresholds as strings like
+//! `"8KiB"` rather than raw integers, since `"1048576"` is much harder for a human to check at a
+//! glance than `"1MiB"`. This module implements the small grammar behind that: a required
+//! (possibly fractional) number followed by an optional unit suffix, using either the decimal SI
+//! multipliers (`KB`, `MB`, ...) or the binary IEC multipliers (`KiB`, `MiB`, ...). A bare number
+//! with no suffix, or the explicit suffix `"B"`, is interpreted as a plain byte count.
+//!
+//! The grammar lives in its own module so it can be unit-tested in isolation from the rest of
+//! the intermediate representation.
+
+use super::ByteSize;
+use crate::prelude::*;
+use std::str::FromStr;
+
+/// The unit suffixes recognized by [`parse`], paired with the number of bytes they multiply by.
+///
+/// Sorted by descending multiplier (interleaving the IEC and SI scales by actual size) so that
+/// [`format`]'s first-exact-match loop really does return the largest unit that represents a
+/// value without loss.
+const UNITS: &[(&str, u64)] = &[
+    ("GiB", 1024 * 1024 * 1024),
+    ("GB", 1_000_000_000),
+    ("MiB", 1024 * 1024),
+    ("MB", 1_000_000),
+    ("KiB", 1024),
+    ("KB", 1_000),
+    ("B", 1),
+];
+
+/// Parse a human-readable byte size such as `"4"`, `"4B"`, `"2KiB"` or `"1.5MiB"`.
+pub fn parse(input: &str) -> Result<ByteSize> {
+    let input = input.trim();
+    let split_at = input
+        .find(|character: char| !character.is_ascii_digit() && character != '.')
+        .unwrap_or(input.len());
+    let (number, unit) = input.split_at(split_at);
+    let number: f64 = number
+        .parse()
+        .map_err(|_| anyhow!("'{input}' does not start with a valid number"))?;
+    let multiplier = if unit.is_empty() {
+        1
+    } else {
+        UNITS
+            .iter()
+            .find(|(suffix, _)| *suffix == unit)
+            .map(|(_, multiplier)| *multiplier)
+            .ok_or_else(|| anyhow!("'{unit}' is not a known byte size unit"))?
+    };
+    Ok(ByteSize::new((number * multiplier as f64).round() as u64))
+}
+
+/// Format `size` using the largest unit, from either the IEC or the SI scale, that represents it
+/// without any loss of precision, falling back to a plain byte count.
+pub fn format(size: ByteSize) -> String {
+    let bytes = u64::from(size);
+    for (suffix, multiplier) in UNITS {
+        if *multiplier > 1 && bytes >= *multiplier && bytes % *multiplier == 0 {
+            return format!("{}{}", bytes / multiplier, suffix);
+        }
+    }
+    format!("{bytes}B")
+}
+
+impl FromStr for ByteSize {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<ByteSize> {
+        parse(input)
+    }
+}
+
+impl std::fmt::Display for ByteSize {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str(&format(*self))
+    }
+}
+
+/// Support for `#[serde(with = "parse")]`, (de)serializing a [`ByteSize`] as its human-readable
+/// string representation instead of a raw integer.
+///
+/// Only compiled in for the human-readable JSON debug path, like every other serde touchpoint in
+/// this module.
+#[cfg(feature = "json")]
+pub fn serialize<S: serde::Serializer>(
+    size: &ByteSize,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    serializer.serialize_str(&format(*size))
+}
+
+/// Support for `#[serde(with = "parse")]`, the `Deserialize` counterpart of [`serialize`].
+#[cfg(feature = "json")]
+pub fn deserialize<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<ByteSize, D::Error> {
+    let string = String::deserialize(deserializer)?;
+    parse(&string).map_err(serde::de::Error::custom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_a_bare_number_as_bytes() {
+        assert_eq!(parse("4").unwrap(), ByteSize::new(4));
+        assert_eq!(parse("4B").unwrap(), ByteSize::new(4));
+    }
+
+    #[test]
+    fn parses_iec_and_si_units() {
+        assert_eq!(parse("2KiB").unwrap(), ByteSize::new(2 * 1024));
+        assert_eq!(parse("1.5MiB").unwrap(), ByteSize::new(1024 * 1024 + 512 * 1024));
+        assert_eq!(parse("2KB").unwrap(), ByteSize::new(2_000));
+    }
+
+    #[test]
+    fn rejects_unknown_units_and_garbage_numbers() {
+        assert!(parse("4XiB").is_err());
+        assert!(parse("KiB").is_err());
+    }
+
+    #[test]
+    fn formats_choose_the_largest_exact_unit() {
+        assert_eq!(format(ByteSize::new(0)), "0B");
+        assert_eq!(format(ByteSize::new(4)), "4B");
+        assert_eq!(format(ByteSize::new(2 * 1024)), "2KiB");
+        assert_eq!(format(ByteSize::new(2 * 1024 * 1024)), "2MiB");
+        // Not evenly divisible by any unit bigger than a byte.
+        assert_eq!(format(ByteSize::new(1025)), "1025B");
+    }
+
+    #[test]
+    fn formats_prefer_the_larger_unit_even_across_iec_and_si_scales() {
+        // 2_000_000_000 is also evenly divisible by KiB (1024), but GB is the far larger exact
+        // divisor and should win.
+        assert_eq!(format(ByteSize::new(2_000_000_000)), "2GB");
+    }
+
+    #[test]
+    fn format_and_parse_round_trip() {
+        for size in [ByteSize::new(0), ByteSize::new(42), ByteSize::new(8 * 1024)] {
+            assert_eq!(parse(&format(size)).unwrap(), size);
+        }
+    }
+}
@@ -0,0 +1,418 @@
+use super::{ByteSize, DatatypeProperties, Expression, FromBytes, ToBytes, Variable};
+use crate::prelude::*;
+use std::collections::BTreeMap;
+
+/// A term identifier, unique to each term in a [`Project`].
+///
+/// The `address` is the address of the machine instruction the term was generated from, and
+/// `id` disambiguates terms that were generated from the same instruction (e.g. the several
+/// `Def`s one CISC instruction may lift to).
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
+pub struct Tid {
+    /// A unique identifier among all IDs with the same address
+    pub id: String,
+    /// The address of the machine instruction that generated this term
+    pub address: String,
+}
+
+impl ToBytes for Tid {
+    fn to_bytes(&self, out: &mut Vec<u8>) {
+        self.id.to_bytes(out);
+        self.address.to_bytes(out);
+    }
+
+    fn count_bytes(&self) -> usize {
+        self.id.count_bytes() + self.address.count_bytes()
+    }
+}
+
+impl FromBytes for Tid {
+    fn from_bytes(input: &mut &[u8]) -> Result<Self> {
+        Ok(Tid {
+            id: String::from_bytes(input)?,
+            address: String::from_bytes(input)?,
+        })
+    }
+}
+
+/// A term is an object (e.g. a basic block or a single instruction) together with a unique
+/// term identifier.
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Term<T> {
+    /// The term identifier, unique among all terms of a [`Project`]
+    pub tid: Tid,
+    /// The actual object
+    pub term: T,
+}
+
+impl<T: ToBytes> ToBytes for Term<T> {
+    fn to_bytes(&self, out: &mut Vec<u8>) {
+        self.tid.to_bytes(out);
+        self.term.to_bytes(out);
+    }
+
+    fn count_bytes(&self) -> usize {
+        self.tid.count_bytes() + self.term.count_bytes()
+    }
+}
+
+impl<T: FromBytes> FromBytes for Term<T> {
+    fn from_bytes(input: &mut &[u8]) -> Result<Self> {
+        Ok(Term {
+            tid: Tid::from_bytes(input)?,
+            term: T::from_bytes(input)?,
+        })
+    }
+}
+
+/// A `Def` evaluates an expression and optionally assigns the result to a variable.
+///
+/// A `Def` carries no memory address and `Expression` has no load variant, so loads from and
+/// stores to memory are not represented.
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Def {
+    /// The variable the result is assigned to, or `None` if it is not assigned to any variable
+    pub lhs: Option<Variable>,
+    /// The expression to evaluate
+    pub rhs: Expression,
+}
+
+impl ToBytes for Def {
+    fn to_bytes(&self, out: &mut Vec<u8>) {
+        self.lhs.to_bytes(out);
+        self.rhs.to_bytes(out);
+    }
+
+    fn count_bytes(&self) -> usize {
+        self.lhs.count_bytes() + self.rhs.count_bytes()
+    }
+}
+
+impl FromBytes for Def {
+    fn from_bytes(input: &mut &[u8]) -> Result<Self> {
+        Ok(Def {
+            lhs: Option::<Variable>::from_bytes(input)?,
+            rhs: Expression::from_bytes(input)?,
+        })
+    }
+}
+
+/// A jump instruction, ending a basic block.
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Jmp {
+    /// An unconditional jump to another basic block
+    Branch(Tid),
+    /// A conditional jump to another basic block
+    CBranch {
+        /// The condition that has to evaluate to `true` for the jump to be taken
+        condition: Expression,
+        /// The target basic block if the condition is `true`
+        target: Tid,
+    },
+    /// A direct call to another function
+    Call {
+        /// The target function
+        target: Tid,
+        /// The basic block execution resumes in after the call returns, if any
+        return_: Option<Tid>,
+    },
+    /// A call through a function pointer
+    CallInd {
+        /// The expression computing the call target
+        target: Expression,
+        /// The basic block execution resumes in after the call returns, if any
+        return_: Option<Tid>,
+    },
+    /// A return from the current function
+    Return(Expression),
+}
+
+impl ToBytes for Jmp {
+    fn to_bytes(&self, out: &mut Vec<u8>) {
+        match self {
+            Jmp::Branch(target) => {
+                out.push(0);
+                target.to_bytes(out);
+            }
+            Jmp::CBranch { condition, target } => {
+                out.push(1);
+                condition.to_bytes(out);
+                target.to_bytes(out);
+            }
+            Jmp::Call { target, return_ } => {
+                out.push(2);
+                target.to_bytes(out);
+                return_.to_bytes(out);
+            }
+            Jmp::CallInd { target, return_ } => {
+                out.push(3);
+                target.to_bytes(out);
+                return_.to_bytes(out);
+            }
+            Jmp::Return(expr) => {
+                out.push(4);
+                expr.to_bytes(out);
+            }
+        }
+    }
+
+    fn count_bytes(&self) -> usize {
+        1 + match self {
+            Jmp::Branch(target) => target.count_bytes(),
+            Jmp::CBranch { condition, target } => condition.count_bytes() + target.count_bytes(),
+            Jmp::Call { target, return_ } => target.count_bytes() + return_.count_bytes(),
+            Jmp::CallInd { target, return_ } => target.count_bytes() + return_.count_bytes(),
+            Jmp::Return(expr) => expr.count_bytes(),
+        }
+    }
+}
+
+impl FromBytes for Jmp {
+    fn from_bytes(input: &mut &[u8]) -> Result<Self> {
+        let (&tag, rest) = input
+            .split_first()
+            .ok_or_else(|| anyhow!("Unexpected end of input while reading a `Jmp` tag byte"))?;
+        *input = rest;
+        Ok(match tag {
+            0 => Jmp::Branch(Tid::from_bytes(input)?),
+            1 => Jmp::CBranch {
+                condition: Expression::from_bytes(input)?,
+                target: Tid::from_bytes(input)?,
+            },
+            2 => Jmp::Call {
+                target: Tid::from_bytes(input)?,
+                return_: Option::<Tid>::from_bytes(input)?,
+            },
+            3 => Jmp::CallInd {
+                target: Expression::from_bytes(input)?,
+                return_: Option::<Tid>::from_bytes(input)?,
+            },
+            4 => Jmp::Return(Expression::from_bytes(input)?),
+            tag => return Err(anyhow!("Invalid tag byte for `Jmp`: {tag}")),
+        })
+    }
+}
+
+/// A basic block: a linear sequence of `Def`s ending in one or more `Jmp`s.
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Blk {
+    /// The sequence of definitions executed in this basic block
+    pub defs: Vec<Term<Def>>,
+    /// The jumps ending the basic block; more than one only for conditional jumps
+    pub jmps: Vec<Term<Jmp>>,
+}
+
+impl ToBytes for Blk {
+    fn to_bytes(&self, out: &mut Vec<u8>) {
+        self.defs.to_bytes(out);
+        self.jmps.to_bytes(out);
+    }
+
+    fn count_bytes(&self) -> usize {
+        self.defs.count_bytes() + self.jmps.count_bytes()
+    }
+}
+
+impl FromBytes for Blk {
+    fn from_bytes(input: &mut &[u8]) -> Result<Self> {
+        Ok(Blk {
+            defs: Vec::<Term<Def>>::from_bytes(input)?,
+            jmps: Vec::<Term<Jmp>>::from_bytes(input)?,
+        })
+    }
+}
+
+/// A function, represented as its control flow graph of basic blocks.
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Sub {
+    /// The name of the function, if known
+    pub name: String,
+    /// The basic blocks making up the function, in the order the disassembler discovered them
+    pub blocks: Vec<Term<Blk>>,
+}
+
+impl ToBytes for Sub {
+    fn to_bytes(&self, out: &mut Vec<u8>) {
+        self.name.to_bytes(out);
+        self.blocks.to_bytes(out);
+    }
+
+    fn count_bytes(&self) -> usize {
+        self.name.count_bytes() + self.blocks.count_bytes()
+    }
+}
+
+impl FromBytes for Sub {
+    fn from_bytes(input: &mut &[u8]) -> Result<Self> {
+        Ok(Sub {
+            name: String::from_bytes(input)?,
+            blocks: Vec::<Term<Blk>>::from_bytes(input)?,
+        })
+    }
+}
+
+/// A whole program, i.e. all functions the disassembler could recover.
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Program {
+    /// All functions, indexed by their term identifier
+    pub subs: BTreeMap<Tid, Term<Sub>>,
+    /// The term identifiers of all functions that serve as entry points, e.g. `main`
+    pub entry_points: Vec<Tid>,
+}
+
+impl ToBytes for Program {
+    fn to_bytes(&self, out: &mut Vec<u8>) {
+        self.subs.to_bytes(out);
+        self.entry_points.to_bytes(out);
+    }
+
+    fn count_bytes(&self) -> usize {
+        self.subs.count_bytes() + self.entry_points.count_bytes()
+    }
+}
+
+impl FromBytes for Program {
+    fn from_bytes(input: &mut &[u8]) -> Result<Self> {
+        Ok(Program {
+            subs: BTreeMap::<Tid, Term<Sub>>::from_bytes(input)?,
+            entry_points: Vec::<Tid>::from_bytes(input)?,
+        })
+    }
+}
+
+/// The `Project` is the main data structure of the intermediate representation.
+///
+/// It contains all information recovered about the binary during the disassembly step.
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Project {
+    /// The program, i.e. all recovered functions
+    pub program: Term<Program>,
+    /// The name of the CPU architecture, e.g. `"x86_64"`
+    pub cpu_architecture: String,
+    /// The variable that holds the stack pointer on this architecture
+    pub stack_pointer_register: Variable,
+    /// The set of variables representing CPU registers on this architecture
+    pub register_set: Vec<Variable>,
+    /// The sizes of the architecture's standard C/C++ datatypes
+    pub datatype_properties: DatatypeProperties,
+}
+
+impl ToBytes for Project {
+    fn to_bytes(&self, out: &mut Vec<u8>) {
+        self.program.to_bytes(out);
+        self.cpu_architecture.to_bytes(out);
+        self.stack_pointer_register.to_bytes(out);
+        self.register_set.to_bytes(out);
+        self.datatype_properties.to_bytes(out);
+    }
+
+    fn count_bytes(&self) -> usize {
+        self.program.count_bytes()
+            + self.cpu_architecture.count_bytes()
+            + self.stack_pointer_register.count_bytes()
+            + self.register_set.count_bytes()
+            + self.datatype_properties.count_bytes()
+    }
+}
+
+impl FromBytes for Project {
+    fn from_bytes(input: &mut &[u8]) -> Result<Self> {
+        Ok(Project {
+            program: Term::<Program>::from_bytes(input)?,
+            cpu_architecture: String::from_bytes(input)?,
+            stack_pointer_register: Variable::from_bytes(input)?,
+            register_set: Vec::<Variable>::from_bytes(input)?,
+            datatype_properties: DatatypeProperties::from_bytes(input)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mock_project() -> Project {
+        let sp = Variable {
+            name: "RSP".into(),
+            size: ByteSize::new(8),
+            is_temp: false,
+        };
+        let blk = Blk {
+            defs: Vec::new(),
+            jmps: vec![Term {
+                tid: Tid {
+                    id: "instr_0x1000_1".into(),
+                    address: "0x1000".into(),
+                },
+                term: Jmp::Return(Expression::Var(sp.clone())),
+            }],
+        };
+        let sub = Sub {
+            name: "main".into(),
+            blocks: vec![Term {
+                tid: Tid {
+                    id: "blk_0x1000".into(),
+                    address: "0x1000".into(),
+                },
+                term: blk,
+            }],
+        };
+        let sub_tid = Tid {
+            id: "sub_0x1000".into(),
+            address: "0x1000".into(),
+        };
+        let mut subs = BTreeMap::new();
+        subs.insert(
+            sub_tid.clone(),
+            Term {
+                tid: sub_tid.clone(),
+                term: sub,
+            },
+        );
+        Project {
+            program: Term {
+                tid: Tid {
+                    id: "program".into(),
+                    address: "0x0".into(),
+                },
+                term: Program {
+                    subs,
+                    entry_points: vec![sub_tid],
+                },
+            },
+            cpu_architecture: "x86_64".into(),
+            stack_pointer_register: sp,
+            register_set: Vec::new(),
+            datatype_properties: DatatypeProperties {
+                char_size: ByteSize::new(1),
+                double_size: ByteSize::new(8),
+                float_size: ByteSize::new(4),
+                integer_size: ByteSize::new(4),
+                long_double_size: ByteSize::new(16),
+                long_long_size: ByteSize::new(8),
+                long_size: ByteSize::new(8),
+                pointer_size: ByteSize::new(8),
+                short_size: ByteSize::new(2),
+            },
+        }
+    }
+
+    #[test]
+    fn binary_round_trip() {
+        let project = mock_project();
+        let mut bytes = Vec::with_capacity(project.count_bytes());
+        project.to_bytes(&mut bytes);
+        assert_eq!(bytes.len(), project.count_bytes());
+        let mut slice = bytes.as_slice();
+        assert_eq!(Project::from_bytes(&mut slice).unwrap(), project);
+        assert!(slice.is_empty());
+    }
+}
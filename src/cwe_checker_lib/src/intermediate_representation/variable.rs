@@ -0,0 +1,62 @@
+use super::{ByteSize, FromBytes, ToBytes};
+use crate::prelude::*;
+
+/// A variable represents a register with a known size and name.
+///
+/// Everything that is not stack or heap memory is represented as a `Variable` in the
+/// intermediate representation, including CPU flags and the temporary registers that the
+/// disassembly frontend synthesizes while lifting a single machine instruction.
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct Variable {
+    /// The name of the register.
+    pub name: String,
+    /// The size in bytes of the register.
+    pub size: ByteSize,
+    /// Is the variable a temporary register,
+    /// i.e. one that only exists for the duration of lifting one machine instruction
+    /// and that is not used to represent actual CPU or memory state?
+    pub is_temp: bool,
+}
+
+impl ToBytes for Variable {
+    fn to_bytes(&self, out: &mut Vec<u8>) {
+        self.name.to_bytes(out);
+        self.size.to_bytes(out);
+        self.is_temp.to_bytes(out);
+    }
+
+    fn count_bytes(&self) -> usize {
+        self.name.count_bytes() + self.size.count_bytes() + self.is_temp.count_bytes()
+    }
+}
+
+impl FromBytes for Variable {
+    fn from_bytes(input: &mut &[u8]) -> Result<Self> {
+        Ok(Variable {
+            name: String::from_bytes(input)?,
+            size: ByteSize::from_bytes(input)?,
+            is_temp: bool::from_bytes(input)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn binary_round_trip() {
+        let var = Variable {
+            name: "RAX".into(),
+            size: ByteSize::new(8),
+            is_temp: false,
+        };
+        let mut bytes = Vec::new();
+        var.to_bytes(&mut bytes);
+        assert_eq!(bytes.len(), var.count_bytes());
+        let mut slice = bytes.as_slice();
+        assert_eq!(Variable::from_bytes(&mut slice).unwrap(), var);
+        assert!(slice.is_empty());
+    }
+}